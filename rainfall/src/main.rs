@@ -51,26 +51,181 @@
  *
  *  - If there are no measurements to read then there is no mean value
  *    to print, so we will print an explanatory message instead.
+ *
+ * ARMORED INPUT
+ *
+ * For transmitting logs through text-only channels, the input may
+ * instead be wrapped in an ASCII-armored block:
+ *
+ *     -----BEGIN RAINFALL-----
+ *     Comment: optional headers go here
+ *
+ *     MTIuNQoxOAo3CjAKNAo=
+ *     -----END RAINFALL-----
+ *
+ * Any `Key: Value` header lines between the BEGIN marker and the
+ * blank line that follows are ignored. The remaining lines up to the
+ * END marker are concatenated and base64-decoded, and the decoded
+ * text is parsed exactly like plain input. The block is detected by
+ * sniffing for the BEGIN marker on the first non-blank line, so plain
+ * unwrapped input keeps working unchanged.
+ *
+ * LARGE INPUT
+ *
+ * Plain input is processed one line at a time without ever
+ * materializing the whole measurement set. Up to 10,000 measurements
+ * this gives an exact summary; beyond that the program switches to a
+ * constant-memory approximation (reservoir-sampled percentiles, and a
+ * below/above classification against the running rather than the
+ * final mean).
+ *
+ * OUTPUT FORMATS
+ *
+ * By default the summary is printed as human-readable text: the three
+ * lines shown under OUTPUT, followed by lines for the minimum, maximum,
+ * median, standard deviation and 25th/75th percentiles. Passing
+ * "--format=json" (or "-j") on the command line switches to a
+ * machine-readable mode that prints one compact JSON object per line,
+ * e.g. `{ "type": "summary", "mean": 8.3, "below": 2, "above": 1,
+ * "count": 5, ... }`, where the elided keys are "min", "max", "median",
+ * "variance", "std_dev", "p25" and "p75".
  */
 
-use std::io::{BufRead,BufReader,Read,stdin,Write,stdout};
+use std::env;
+use std::io::{self,BufRead,BufReader,ErrorKind,Read,stdin,Write,stdout};
+use std::process::exit;
 
 fn main() {
-    let measurements = read_measurements(stdin());
-    write_output(stdout(), &calculate_results(&measurements));
+    let format = Format::from_args(env::args());
+
+    let results = match read_results(stdin()) {
+        Ok(results) => results,
+        Err(e) => {
+            eprintln!("rainfall: error reading measurements: {}", e);
+            exit(1);
+        }
+    };
+
+    write_output(stdout(), format, &results);
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Format {
+    Human,
+    Json,
+}
+
+impl Format {
+    fn from_args<I: Iterator<Item = String>>(args: I) -> Format {
+        for arg in args {
+            if arg == "-j" || arg == "--format=json" {
+                return Format::Json;
+            }
+        }
+        Format::Human
+    }
 }
 
+#[cfg(test)]
+mod format_tests {
+    use super::Format;
+
+    #[test]
+    fn defaults_to_human() {
+        let args = vec!["rainfall".to_string()];
+        assert_eq!(Format::Human, Format::from_args(args.into_iter()));
+    }
+
+    #[test]
+    fn dash_j_selects_json() {
+        let args = vec!["rainfall".to_string(), "-j".to_string()];
+        assert_eq!(Format::Json, Format::from_args(args.into_iter()));
+    }
+
+    #[test]
+    fn long_flag_selects_json() {
+        let args = vec!["rainfall".to_string(), "--format=json".to_string()];
+        assert_eq!(Format::Json, Format::from_args(args.into_iter()));
+    }
+}
+
+#[derive(Debug)]
 struct Results {
-    mean:  f64,
-    above: usize,
-    below: usize,
+    mean:     f64,
+    above:    usize,
+    below:    usize,
+    count:    usize,
+    min:      f64,
+    max:      f64,
+    median:   f64,
+    variance: f64,
+    std_dev:  f64,
+    p25:      f64,
+    p75:      f64,
 }
 
-fn read_measurements<R: Read>(reader: R) -> Vec<f64> {
+/// Reads measurements from `reader` and reduces them straight to a
+/// `Results`, without ever materializing the full input as a `Vec`
+/// for plain (unarmored) input: lines are fed one at a time into an
+/// `Accumulator`, which switches from an exact to an approximate
+/// running summary once `STREAMING_THRESHOLD` values have been seen.
+/// An armored block is still read in full before being decoded, since
+/// its base64 body must be concatenated before it can be decoded.
+fn read_results<R: Read>(reader: R) -> io::Result<Results> {
+    let mut lines = ResultLines::new(reader);
+
+    let first_non_blank = loop {
+        match lines.next()? {
+            None => return Ok(calculate_results(&[])),
+            Some(line) => if !line.is_empty() { break line },
+        }
+    };
+
+    if first_non_blank == ARMOR_BEGIN {
+        let mut armored = vec![first_non_blank];
+        while let Some(line) = lines.next()? {
+            armored.push(line);
+        }
+        let decoded = decode_armor(&armored)?;
+        let measurements = parse_measurements(decoded.lines().map(str::to_string));
+        return Ok(calculate_results(&measurements));
+    }
+
+    let mut acc = Accumulator::new();
+    if acc.push(&first_non_blank) {
+        while let Some(line) = lines.next()? {
+            if !acc.push(&line) {break}
+        }
+    }
+
+    Ok(acc.finish())
+}
+
+/// A thin wrapper around `BufRead::lines()` that surfaces I/O errors
+/// through an `io::Result` instead of yielding them as iterator items.
+/// (`BufRead::lines()` already retries a spurious `ErrorKind::Interrupted`
+/// internally, at the `fill_buf` layer, so there is nothing left for
+/// this wrapper to retry.)
+struct ResultLines<R: Read> {
+    lines: io::Lines<BufReader<R>>,
+}
+
+impl<R: Read> ResultLines<R> {
+    fn new(reader: R) -> Self {
+        ResultLines { lines: BufReader::new(reader).lines() }
+    }
+
+    fn next(&mut self) -> io::Result<Option<String>> {
+        self.lines.next().transpose()
+    }
+}
+
+/// Extracts measurements out of already-split lines, ignoring noise
+/// and stopping at a "999" terminator line.
+fn parse_measurements<I: Iterator<Item = String>>(lines: I) -> Vec<f64> {
     let mut measurements: Vec<f64> = vec![]; // Vec::new()
-    let mut lines = BufReader::new(reader).lines();
 
-    while let Some(Ok(line)) = lines.next() {
+    for line in lines {
         if line == "999" {break}
 
         if let Ok(f) = line.parse() {
@@ -80,13 +235,125 @@ fn read_measurements<R: Read>(reader: R) -> Vec<f64> {
         }
     }
 
-    return measurements;
+    measurements
+}
+
+const ARMOR_BEGIN: &str = "-----BEGIN RAINFALL-----";
+const ARMOR_END:   &str = "-----END RAINFALL-----";
+
+/// Strips the armor off `lines` (whose first element must be
+/// `ARMOR_BEGIN`), swallowing any `Key: Value` header lines, and
+/// base64-decodes the body into the plain text it wraps.
+fn decode_armor(lines: &[String]) -> io::Result<String> {
+    let mut i = 1;
+
+    while i < lines.len() && !lines[i].is_empty() {
+        i += 1; // swallow a header line
+    }
+    if i >= lines.len() {
+        return Err(armor_error("missing blank line after headers"));
+    }
+    i += 1; // skip the blank line itself
+
+    let mut body = String::new();
+    let mut found_end = false;
+    while i < lines.len() {
+        if lines[i] == ARMOR_END {
+            found_end = true;
+            break;
+        }
+        body.push_str(&lines[i]);
+        i += 1;
+    }
+    if !found_end {
+        return Err(armor_error("missing END marker"));
+    }
+
+    let bytes = base64_decode(&body).map_err(armor_error)?;
+    String::from_utf8(bytes).map_err(|e| armor_error(e.to_string()))
+}
+
+fn armor_error<E: ToString>(reason: E) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData,
+                   format!("malformed armored rainfall block: {}", reason.to_string()))
+}
+
+/// Decodes a standard (RFC 4648) base64 string, ignoring embedded
+/// newlines.
+fn base64_decode(input: &str) -> Result<Vec<u8>, String> {
+    const ALPHABET: &[u8] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    fn value(byte: u8) -> Option<u8> {
+        ALPHABET.iter().position(|&b| b == byte).map(|p| p as u8)
+    }
+
+    let chars: Vec<u8> = input.bytes().filter(|&b| b != b'\n' && b != b'\r').collect();
+    if chars.is_empty() {
+        return Ok(vec![]);
+    }
+    if !chars.len().is_multiple_of(4) {
+        return Err("base64 length is not a multiple of 4".to_string());
+    }
+
+    let mut out = Vec::with_capacity(chars.len() / 4 * 3);
+    for chunk in chars.chunks(4) {
+        let mut vals = [0u8; 4];
+        let mut pad = 0;
+
+        for (i, &c) in chunk.iter().enumerate() {
+            if c == b'=' {
+                pad += 1;
+            } else {
+                if pad > 0 {
+                    return Err("base64 padding before end of input".to_string());
+                }
+                vals[i] = value(c).ok_or_else(|| format!("invalid base64 character {:?}", c as char))?;
+            }
+        }
+        if pad > 2 {
+            return Err("too much base64 padding".to_string());
+        }
+
+        out.push((vals[0] << 2) | (vals[1] >> 4));
+        if pad < 2 { out.push((vals[1] << 4) | (vals[2] >> 2)); }
+        if pad < 1 { out.push((vals[2] << 6) | vals[3]); }
+    }
+
+    Ok(out)
 }
 
 #[cfg(test)]
-mod read_measurements_tests {
-    use super::read_measurements;
-    use std::io::Cursor;
+mod base64_decode_tests {
+    use super::base64_decode;
+
+    #[test]
+    fn decodes_padded_input() {
+        assert_eq!(b"Many".to_vec(), base64_decode("TWFueQ==").unwrap());
+        assert_eq!(b"hello".to_vec(), base64_decode("aGVsbG8=").unwrap());
+    }
+
+    #[test]
+    fn decodes_multiline_body() {
+        assert_eq!(b"hello, world".to_vec(),
+                   base64_decode("aGVsbG8s\nIHdvcmxk").unwrap());
+    }
+
+    #[test]
+    fn rejects_bad_length() {
+        assert!(base64_decode("abc").is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_character() {
+        assert!(base64_decode("ab!=").is_err());
+    }
+}
+
+#[cfg(test)]
+mod read_results_tests {
+    use super::{calculate_results, read_results};
+    use std::io::{Cursor,Error,ErrorKind,Read};
 
     #[test]
     fn reads_three_measurements() {
@@ -108,22 +375,394 @@ mod read_measurements_tests {
         assert_read(&[3., 4.], "3\n4\n999\n5\n");
     }
 
+    #[test]
+    fn survives_an_interrupted_read() {
+        let reader = InterruptOnceThenRead::new("3\n4\n5\n");
+        let results = read_results(reader).unwrap();
+        assert_eq!(calculate_results(&[3., 4., 5.]).mean, results.mean);
+        assert_eq!(3, results.count);
+    }
+
+    #[test]
+    fn surfaces_hard_errors() {
+        let reader = ErrorMidStream::new("3\n4\n");
+        let err = read_results(reader).unwrap_err();
+        assert_eq!(ErrorKind::Other, err.kind());
+    }
+
+    #[test]
+    fn reads_armored_block() {
+        assert_read(&[12.5, 18., 7., 0., 4.],
+                    "-----BEGIN RAINFALL-----\n\
+                     \n\
+                     MTIuNQoxOAo3CjAKNAo=\n\
+                     -----END RAINFALL-----\n");
+    }
+
+    #[test]
+    fn reads_armored_block_with_headers() {
+        assert_read(&[12.5, 18., 7., 0., 4.],
+                    "-----BEGIN RAINFALL-----\n\
+                     Comment: from a field sensor\n\
+                     Version: 1\n\
+                     \n\
+                     MTIuNQoxOAo3CjAKNAo=\n\
+                     -----END RAINFALL-----\n");
+    }
+
+    #[test]
+    fn rejects_truncated_armored_block() {
+        let mock_read = Cursor::new(
+            "-----BEGIN RAINFALL-----\n\
+             MTIuNQoxOAo3CjAKNAo=\n");
+        assert_eq!(ErrorKind::InvalidData,
+                   read_results(mock_read).unwrap_err().kind());
+    }
+
     fn assert_read(expected: &[f64], input: &str) {
         let mock_read = Cursor::new(input);
-        let measurements = read_measurements(mock_read);
-        assert_eq!(expected.to_owned(), measurements);
+        let actual = read_results(mock_read).unwrap();
+        let want = calculate_results(expected);
+        assert_eq!(want.mean, actual.mean);
+        assert_eq!(want.below, actual.below);
+        assert_eq!(want.above, actual.above);
+        assert_eq!(want.count, actual.count);
+        assert_eq!(want.median, actual.median);
+    }
+
+    /// A mock reader that reports `ErrorKind::Interrupted` on its first
+    /// call, then yields `data` as normal. Documents that a spurious
+    /// `Interrupted` doesn't surface as an error or truncate the
+    /// input: `BufRead::lines()` already retries it internally, at the
+    /// `fill_buf` layer, before it would ever reach `ResultLines`.
+    struct InterruptOnceThenRead {
+        data:        Cursor<String>,
+        interrupted: bool,
+    }
+
+    impl InterruptOnceThenRead {
+        fn new(data: &str) -> Self {
+            InterruptOnceThenRead {
+                data:        Cursor::new(data.to_string()),
+                interrupted: false,
+            }
+        }
+    }
+
+    impl Read for InterruptOnceThenRead {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if !self.interrupted {
+                self.interrupted = true;
+                return Err(Error::new(ErrorKind::Interrupted, "interrupted"));
+            }
+            self.data.read(buf)
+        }
+    }
+
+    /// A mock reader that yields `data`, then reports a hard error.
+    struct ErrorMidStream {
+        data: Cursor<String>,
+        done: bool,
+    }
+
+    impl ErrorMidStream {
+        fn new(data: &str) -> Self {
+            ErrorMidStream {
+                data: Cursor::new(data.to_string()),
+                done: false,
+            }
+        }
+    }
+
+    impl Read for ErrorMidStream {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.done {
+                return Err(Error::other("hard failure"));
+            }
+            let n = self.data.read(buf)?;
+            if n == 0 {
+                self.done = true;
+                return Err(Error::other("hard failure"));
+            }
+            Ok(n)
+        }
+    }
+}
+
+/// Above this many retained values, `Accumulator` stops storing every
+/// measurement and switches to a constant-memory, approximate
+/// summary.
+const STREAMING_THRESHOLD: usize = 10_000;
+
+/// Number of samples kept for approximate percentile estimation once
+/// an `Accumulator` has switched to streaming mode.
+const RESERVOIR_SIZE: usize = 1_000;
+
+/// Reduces a sequence of measurements to a `Results`, storing every
+/// value (for an exact summary) while the count stays at or below
+/// `STREAMING_THRESHOLD`, then transparently switching to a streaming,
+/// approximate summary for the rest of the input.
+enum Accumulator {
+    Stored(Vec<f64>),
+    Streaming(Streaming),
+}
+
+impl Accumulator {
+    fn new() -> Self {
+        Accumulator::Stored(vec![])
+    }
+
+    /// Feeds one more raw input line in. Returns `false` once the
+    /// "999" terminator line has been seen, at which point the caller
+    /// should stop reading.
+    fn push(&mut self, line: &str) -> bool {
+        if line == "999" {return false}
+
+        if let Ok(f) = line.parse() {
+            if f >= 0.0 {
+                self.push_value(f);
+            }
+        }
+
+        true
+    }
+
+    fn push_value(&mut self, x: f64) {
+        if let Accumulator::Stored(values) = self {
+            values.push(x);
+            if values.len() > STREAMING_THRESHOLD {
+                let values = std::mem::take(values);
+                *self = Accumulator::Streaming(Streaming::from_values(values));
+            }
+            return;
+        }
+
+        if let Accumulator::Streaming(streaming) = self {
+            streaming.push(x);
+        }
+    }
+
+    fn finish(self) -> Results {
+        match self {
+            Accumulator::Stored(values)  => calculate_results(&values),
+            Accumulator::Streaming(s)    => s.into_results(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod accumulator_tests {
+    use super::{Accumulator, STREAMING_THRESHOLD};
+
+    // Kept well clear of 999, which `Accumulator::push` treats as the
+    // end-of-input terminator rather than a value.
+    const OFFSET: usize = 1_000_000;
+
+    #[test]
+    fn stays_stored_at_or_below_threshold() {
+        let mut acc = Accumulator::new();
+        for i in 0..STREAMING_THRESHOLD {
+            acc.push(&(OFFSET + i).to_string());
+        }
+        match acc {
+            Accumulator::Stored(ref values) => assert_eq!(STREAMING_THRESHOLD, values.len()),
+            Accumulator::Streaming(_)       => panic!("expected stored mode"),
+        }
+    }
+
+    #[test]
+    fn switches_to_streaming_past_threshold() {
+        let mut acc = Accumulator::new();
+        for i in 0..(STREAMING_THRESHOLD + 1) {
+            acc.push(&(OFFSET + i).to_string());
+        }
+        match acc {
+            Accumulator::Streaming(ref s) => assert_eq!(STREAMING_THRESHOLD + 1, s.count),
+            Accumulator::Stored(_)        => panic!("expected streaming mode"),
+        }
+    }
+
+    #[test]
+    fn streaming_mode_keeps_an_exact_count_and_an_approximate_mean() {
+        let n = STREAMING_THRESHOLD + 500;
+        let mut acc = Accumulator::new();
+        for i in 0..n {
+            acc.push(&(OFFSET + i).to_string());
+        }
+
+        let results = acc.finish();
+        let exact_mean = (OFFSET as f64) + ((n - 1) as f64) / 2.0;
+        assert_eq!(n, results.count);
+        assert!((results.mean - exact_mean).abs() < 1e-6);
+    }
+
+    #[test]
+    fn stops_at_999_in_streaming_mode() {
+        let mut acc = Accumulator::new();
+        for i in 0..(STREAMING_THRESHOLD + 1) {
+            assert!(acc.push(&(OFFSET + i).to_string()));
+        }
+        assert!(!acc.push("999"));
+
+        let results = acc.finish();
+        assert_eq!(STREAMING_THRESHOLD + 1, results.count);
+    }
+}
+
+/// A running, constant-memory approximation of `Results`: exact count,
+/// mean, variance, min and max (via Welford's algorithm), but
+/// reservoir-sampled percentiles and a below/above classification
+/// against the running (rather than final) mean.
+struct Streaming {
+    count:     usize,
+    mean:      f64,
+    m2:        f64,
+    min:       f64,
+    max:       f64,
+    below:     usize,
+    above:     usize,
+    reservoir: Vec<f64>,
+    rng:       Xorshift64,
+}
+
+impl Streaming {
+    fn from_values(values: Vec<f64>) -> Self {
+        let mut streaming = Streaming {
+            count:     0,
+            mean:      0.0,
+            m2:        0.0,
+            min:       f64::INFINITY,
+            max:       f64::NEG_INFINITY,
+            below:     0,
+            above:     0,
+            reservoir: vec![],
+            rng:       Xorshift64::new(0x9E3779B97F4A7C15),
+        };
+        for x in values {
+            streaming.push(x);
+        }
+        streaming
+    }
+
+    fn push(&mut self, x: f64) {
+        self.count += 1;
+        let delta = x - self.mean;
+        self.mean += delta / (self.count as f64);
+        self.m2 += delta * (x - self.mean);
+
+        self.min = self.min.min(x);
+        self.max = self.max.max(x);
+
+        if self.reservoir.len() < RESERVOIR_SIZE {
+            self.reservoir.push(x);
+        } else {
+            let j = self.rng.next_below(self.count as u64) as usize;
+            if j < RESERVOIR_SIZE {
+                self.reservoir[j] = x;
+            }
+        }
+
+        if self.mean - 5.0 <= x && x < self.mean {
+            self.below += 1;
+        } else if self.mean < x && x <= self.mean + 5.0 {
+            self.above += 1;
+        }
+    }
+
+    fn into_results(self) -> Results {
+        let variance = if self.count < 2 {
+            f64::NAN
+        } else {
+            self.m2 / ((self.count - 1) as f64)
+        };
+
+        let mut sorted = self.reservoir;
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        Results {
+            mean:   self.mean,
+            above:  self.above,
+            below:  self.below,
+            count:  self.count,
+            min:    self.min,
+            max:    self.max,
+            median: percentile(&sorted, 50.0),
+            variance,
+            std_dev: variance.sqrt(),
+            p25:     percentile(&sorted, 25.0),
+            p75:     percentile(&sorted, 75.0),
+        }
+    }
+}
+
+/// A small, deterministic xorshift64 PRNG, good enough for reservoir
+/// sampling but not for anything security-sensitive.
+struct Xorshift64 {
+    state: u64,
+}
+
+impl Xorshift64 {
+    fn new(seed: u64) -> Self {
+        Xorshift64 { state: if seed == 0 {0xdead_beef} else {seed} }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.state = x;
+        x
+    }
+
+    /// Returns a uniformly distributed value in `0..bound`.
+    fn next_below(&mut self, bound: u64) -> u64 {
+        if bound == 0 {0} else {self.next_u64() % bound}
+    }
+}
+
+#[cfg(test)]
+mod xorshift64_tests {
+    use super::Xorshift64;
+
+    #[test]
+    fn next_below_stays_in_range() {
+        let mut rng = Xorshift64::new(42);
+        for _ in 0..1000 {
+            assert!(rng.next_below(7) < 7);
+        }
+    }
+
+    #[test]
+    fn is_deterministic_for_a_given_seed() {
+        let mut a = Xorshift64::new(42);
+        let mut b = Xorshift64::new(42);
+        for _ in 0..10 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
     }
 }
 
 fn calculate_results(fs: &[f64]) -> Results {
-    let m = mean(fs);
+    let (count, m, variance) = welford(fs);
     let b = fs.iter().filter(|&&x| m - 5.0 <= x && x < m).count();
     let a = fs.iter().filter(|&&x| m < x && x <= m + 5.0).count();
 
+    let mut sorted = fs.to_owned();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
     Results {
-        mean:  m,
-        above: a,
-        below: b,
+        mean:   m,
+        above:  a,
+        below:  b,
+        count,
+        min:    sorted.first().cloned().unwrap_or(f64::NAN),
+        max:    sorted.last().cloned().unwrap_or(f64::NAN),
+        median: percentile(&sorted, 50.0),
+        variance,
+        std_dev: variance.sqrt(),
+        p25:     percentile(&sorted, 25.0),
+        p75:     percentile(&sorted, 75.0),
     }
 }
 
@@ -138,79 +777,251 @@ mod calculate_results_tests {
         assert_eq!(8.3, result.mean);
         assert_eq!(1, result.above);
         assert_eq!(2, result.below);
+        assert_eq!(5, result.count);
+        assert_eq!(0., result.min);
+        assert_eq!(18., result.max);
+        assert_eq!(7., result.median);
+        assert_eq!(4., result.p25);
+        assert_eq!(12.5, result.p75);
     }
 }
 
-fn mean(samples: &[f64]) -> f64 {
-    sum(samples) / (samples.len() as f64)
+/// Computes the count, mean, and sample variance of `samples` in a
+/// single pass using Welford's online algorithm.
+fn welford(samples: &[f64]) -> (usize, f64, f64) {
+    let mut count = 0usize;
+    let mut mean  = 0.0;
+    let mut m2    = 0.0;
+
+    for &x in samples {
+        count += 1;
+        let delta = x - mean;
+        mean += delta / (count as f64);
+        m2 += delta * (x - mean);
+    }
+
+    let variance = if count < 2 {
+        f64::NAN
+    } else {
+        m2 / ((count - 1) as f64)
+    };
+
+    if count == 0 {
+        (count, f64::NAN, variance)
+    } else {
+        (count, mean, variance)
+    }
 }
 
 #[cfg(test)]
-mod mean_tests {
-    use super::mean;
+mod welford_tests {
+    use super::welford;
 
     #[test]
-    fn mean_empty_is_nan() {
-        assert!(mean(&[]).is_nan());
+    fn empty_is_nan() {
+        let (count, mean, variance) = welford(&[]);
+        assert_eq!(0, count);
+        assert!(mean.is_nan());
+        assert!(variance.is_nan());
     }
 
     #[test]
-    fn mean_2_3_4_is_3() {
-        assert_eq!(3.0, mean(&[2., 3., 4.]));
+    fn single_sample_has_nan_variance() {
+        let (count, mean, variance) = welford(&[3.]);
+        assert_eq!(1, count);
+        assert_eq!(3., mean);
+        assert!(variance.is_nan());
+    }
+
+    #[test]
+    fn matches_mean_and_sample_variance() {
+        let (count, mean, variance) = welford(&[2., 4., 4., 4., 5., 5., 7., 9.]);
+        assert_eq!(8, count);
+        assert_eq!(5., mean);
+        assert_eq!(4.571428571428571, variance);
     }
 }
 
-fn sum(samples: &[f64]) -> f64 {
-    samples.iter().fold(0.0, |a,b| a + *b)
+/// Computes the `p`-th percentile (0..=100) of `sorted`, which must
+/// already be sorted in ascending order, linearly interpolating
+/// between the two nearest ranks.
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return f64::NAN;
+    }
+    if sorted.len() == 1 {
+        return sorted[0];
+    }
+
+    let rank = (p / 100.0) * ((sorted.len() - 1) as f64);
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+
+    if lower == upper {
+        sorted[lower]
+    } else {
+        let fraction = rank - (lower as f64);
+        sorted[lower] + fraction * (sorted[upper] - sorted[lower])
+    }
 }
 
 #[cfg(test)]
-mod sum_tests {
-    use super::sum;
+mod percentile_tests {
+    use super::percentile;
+
+    #[test]
+    fn empty_is_nan() {
+        assert!(percentile(&[], 50.0).is_nan());
+    }
+
+    #[test]
+    fn single_value() {
+        assert_eq!(3., percentile(&[3.], 50.0));
+    }
+
+    #[test]
+    fn median_of_odd_length() {
+        assert_eq!(4., percentile(&[1., 3., 4., 7., 9.], 50.0));
+    }
 
     #[test]
-    fn sum_empty_is_0() {
-        assert_eq!(0.0, sum(&[]));
+    fn median_of_even_length_interpolates() {
+        assert_eq!(3.5, percentile(&[1., 3., 4., 7.], 50.0));
     }
 
     #[test]
-    fn sum_1_2_3_4_is_10() {
-        assert_eq!(10.0, sum(&[1., 2., 3., 4.]));
+    fn quartiles() {
+        let sorted = [1., 2., 3., 4., 5., 6., 7., 8., 9.];
+        assert_eq!(3., percentile(&sorted, 25.0));
+        assert_eq!(7., percentile(&sorted, 75.0));
     }
 }
 
-fn write_output<W: Write>(mut writer: W, r: &Results) {
+fn write_output<W: Write>(writer: W, format: Format, r: &Results) {
+    match format {
+        Format::Human => write_human(writer, r),
+        Format::Json  => write_json(writer, r),
+    }
+}
+
+fn write_human<W: Write>(mut writer: W, r: &Results) {
   if r.mean.is_nan() {
-      write!(writer, "No measurements provided.\n").unwrap();
+      writeln!(writer, "No measurements provided.").unwrap();
   } else {
-      write!(writer, "Mean rainfall: {} cm\n", r.mean).unwrap();
-      write!(writer, "Below count:   {}\n", r.below).unwrap();
-      write!(writer, "Above count:   {}\n", r.above).unwrap();
+      writeln!(writer, "Mean rainfall: {} cm", r.mean).unwrap();
+      writeln!(writer, "Below count:   {}", r.below).unwrap();
+      writeln!(writer, "Above count:   {}", r.above).unwrap();
+      writeln!(writer, "Min:           {} cm", r.min).unwrap();
+      writeln!(writer, "Max:           {} cm", r.max).unwrap();
+      writeln!(writer, "Median:        {} cm", r.median).unwrap();
+      writeln!(writer, "Std dev:       {}", r.std_dev).unwrap();
+      writeln!(writer, "25th pctile:   {} cm", r.p25).unwrap();
+      writeln!(writer, "75th pctile:   {} cm", r.p75).unwrap();
   }
 }
 
+fn write_json<W: Write>(mut writer: W, r: &Results) {
+    if r.mean.is_nan() {
+        writeln!(writer, "{{ \"type\": \"summary\", \"measurements\": 0 }}").unwrap();
+    } else {
+        writeln!(writer,
+                 "{{ \"type\": \"summary\", \"mean\": {}, \"below\": {}, \"above\": {}, \"count\": {}, \
+                  \"min\": {}, \"max\": {}, \"median\": {}, \"variance\": {}, \"std_dev\": {}, \
+                  \"p25\": {}, \"p75\": {} }}",
+                 r.mean, r.below, r.above, r.count,
+                 r.min, r.max, r.median, json_number(r.variance), json_number(r.std_dev),
+                 r.p25, r.p75).unwrap();
+    }
+}
+
+/// Formats a float for a JSON document, rendering `NaN` as `null` since
+/// a bareword `NaN` is not valid JSON. `variance`/`std_dev` are the
+/// only fields that can still be NaN outside the empty-input case
+/// handled above (a single measurement has no sample variance).
+fn json_number(x: f64) -> String {
+    if x.is_nan() { "null".to_string() } else { x.to_string() }
+}
+
 #[cfg(test)]
 mod write_output_tests {
-    use super::{write_output, Results};
+    use super::{write_output, Format, Results};
     use std::io::Cursor;
 
     #[test]
     fn no_measurements_output() {
-        use std::f64::NAN;
-        assert_write("No measurements provided.\n",
-                     &Results { mean:  NAN, above: 0, below: 0 });
+        assert_write(Format::Human, "No measurements provided.\n", &nan_results());
     }
 
     #[test]
     fn some_measurements_output() {
         assert_write(
-            "Mean rainfall: 5 cm\nBelow count:   3\nAbove count:   2\n",
-            &Results { mean:  5., above: 2, below: 3 });
+            Format::Human,
+            "Mean rainfall: 5 cm\n\
+             Below count:   3\n\
+             Above count:   2\n\
+             Min:           0 cm\n\
+             Max:           10 cm\n\
+             Median:        5 cm\n\
+             Std dev:       2\n\
+             25th pctile:   3 cm\n\
+             75th pctile:   7 cm\n",
+            &sample_results());
+    }
+
+    #[test]
+    fn no_measurements_json_output() {
+        assert_write(Format::Json,
+                     "{ \"type\": \"summary\", \"measurements\": 0 }\n",
+                     &nan_results());
+    }
+
+    #[test]
+    fn some_measurements_json_output() {
+        assert_write(
+            Format::Json,
+            "{ \"type\": \"summary\", \"mean\": 5, \"below\": 3, \"above\": 2, \"count\": 5, \
+             \"min\": 0, \"max\": 10, \"median\": 5, \"variance\": 4, \"std_dev\": 2, \
+             \"p25\": 3, \"p75\": 7 }\n",
+            &sample_results());
+    }
+
+    #[test]
+    fn single_measurement_json_output_has_no_bareword_nan() {
+        assert_write(
+            Format::Json,
+            "{ \"type\": \"summary\", \"mean\": 5, \"below\": 0, \"above\": 0, \"count\": 1, \
+             \"min\": 5, \"max\": 5, \"median\": 5, \"variance\": null, \"std_dev\": null, \
+             \"p25\": 5, \"p75\": 5 }\n",
+            &single_results());
+    }
+
+    fn nan_results() -> Results {
+        Results {
+            mean: f64::NAN, above: 0, below: 0, count: 0,
+            min: f64::NAN, max: f64::NAN, median: f64::NAN, variance: f64::NAN, std_dev: f64::NAN,
+            p25: f64::NAN, p75: f64::NAN,
+        }
+    }
+
+    fn single_results() -> Results {
+        Results {
+            mean: 5., above: 0, below: 0, count: 1,
+            min: 5., max: 5., median: 5., variance: f64::NAN, std_dev: f64::NAN,
+            p25: 5., p75: 5.,
+        }
+    }
+
+    fn sample_results() -> Results {
+        Results {
+            mean: 5., above: 2, below: 3, count: 5,
+            min: 0., max: 10., median: 5., variance: 4., std_dev: 2.,
+            p25: 3., p75: 7.,
+        }
     }
 
-    fn assert_write(expected: &str, results: &Results) {
+    fn assert_write(format: Format, expected: &str, results: &Results) {
         let mut writer = Cursor::new(vec![]);
-        write_output(&mut writer, results);
+        write_output(&mut writer, format, results);
         assert_eq!(expected.as_bytes(), &*writer.into_inner());
     }
 }